@@ -0,0 +1,266 @@
+use crate::PrinterData;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// One persisted observation of a printer's state, tagged with the printer
+/// it was collected from so a single history file can back multiple printers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HistorySnapshot {
+    printer_url: String,
+    #[serde(flatten)]
+    data: PrinterData,
+}
+
+fn get_history_path() -> Result<PathBuf> {
+    if let Some(config_dir) = dirs::config_dir() {
+        let hp_config_dir = config_dir.join("hp-instant-ink");
+        Ok(hp_config_dir.join("history.jsonl"))
+    } else {
+        anyhow::bail!("Could not determine config directory")
+    }
+}
+
+/// Append a snapshot of `data` for `printer_url` to the history file.
+pub fn append_snapshot(printer_url: &str, data: &PrinterData) -> Result<()> {
+    let history_path = get_history_path()?;
+
+    if let Some(parent) = history_path.parent() {
+        fs::create_dir_all(parent).context("Failed to create config directory")?;
+    }
+
+    let snapshot = HistorySnapshot {
+        printer_url: printer_url.to_string(),
+        data: data.clone(),
+    };
+    let line = serde_json::to_string(&snapshot).context("Failed to serialize history snapshot")?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&history_path)
+        .context("Failed to open history file")?;
+
+    writeln!(file, "{line}").context("Failed to write history snapshot")?;
+
+    Ok(())
+}
+
+/// Load every snapshot recorded for `printer_url`, oldest first.
+pub fn load_snapshots(printer_url: &str) -> Result<Vec<PrinterData>> {
+    let history_path = get_history_path()?;
+
+    if !history_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&history_path).context("Failed to read history file")?;
+
+    let mut snapshots: Vec<PrinterData> = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(
+            |line| match serde_json::from_str::<HistorySnapshot>(line) {
+                Ok(snapshot) if snapshot.printer_url == printer_url => Some(snapshot.data),
+                Ok(_) => None,
+                Err(e) => {
+                    warn!("Skipping malformed history entry: {e}");
+                    None
+                }
+            },
+        )
+        .collect();
+
+    snapshots.sort_by_key(|data| data.timestamp);
+
+    Ok(snapshots)
+}
+
+/// Average percent-per-second drop across `snapshots`, restricted to the
+/// window since the most recent rise (a cartridge replacement/refill) so a
+/// fresh cartridge doesn't get averaged in with the depleted one before it.
+/// Rises within the window are otherwise ignored rather than subtracted.
+fn depletion_rate_per_second(
+    snapshots: &[PrinterData],
+    level_of: impl Fn(&PrinterData) -> u32,
+) -> Option<f64> {
+    let mut window_start = 0;
+    for i in 1..snapshots.len() {
+        if level_of(&snapshots[i]) > level_of(&snapshots[i - 1]) {
+            window_start = i;
+        }
+    }
+
+    let window = &snapshots[window_start..];
+    if window.len() < 2 {
+        return None;
+    }
+
+    let mut total_drop_percent = 0f64;
+    let mut total_elapsed_seconds = 0f64;
+
+    for pair in window.windows(2) {
+        let (prev, now) = (&pair[0], &pair[1]);
+        let prev_level = level_of(prev) as f64;
+        let now_level = level_of(now) as f64;
+        if now_level < prev_level {
+            let elapsed = (now.timestamp - prev.timestamp).num_seconds() as f64;
+            if elapsed > 0.0 {
+                total_drop_percent += prev_level - now_level;
+                total_elapsed_seconds += elapsed;
+            }
+        }
+    }
+
+    if total_elapsed_seconds <= 0.0 {
+        None
+    } else {
+        Some(total_drop_percent / total_elapsed_seconds)
+    }
+}
+
+/// Average forward delta per day for a monotonically increasing counter.
+fn rate_per_day(snapshots: &[PrinterData], value_of: impl Fn(&PrinterData) -> u32) -> Option<f64> {
+    if snapshots.len() < 2 {
+        return None;
+    }
+
+    let mut total_delta = 0f64;
+    let mut total_elapsed_seconds = 0f64;
+
+    for pair in snapshots.windows(2) {
+        let (prev, now) = (&pair[0], &pair[1]);
+        let prev_value = value_of(prev) as f64;
+        let now_value = value_of(now) as f64;
+        let elapsed = (now.timestamp - prev.timestamp).num_seconds() as f64;
+        if now_value >= prev_value && elapsed > 0.0 {
+            total_delta += now_value - prev_value;
+            total_elapsed_seconds += elapsed;
+        }
+    }
+
+    if total_elapsed_seconds <= 0.0 {
+        None
+    } else {
+        Some(total_delta / total_elapsed_seconds * 86400.0)
+    }
+}
+
+/// Printing-rate and ink-depletion projections derived from a printer's
+/// snapshot history. `None` fields mean there wasn't enough data yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryReport {
+    pub snapshot_count: usize,
+    pub pages_per_day: Option<f64>,
+    pub subscription_impressions_per_day: Option<f64>,
+    pub black_ink_days_remaining: Option<f64>,
+    pub colour_ink_days_remaining: Option<f64>,
+    pub black_ink_runs_out: Option<DateTime<Utc>>,
+    pub colour_ink_runs_out: Option<DateTime<Utc>>,
+}
+
+impl HistoryReport {
+    /// Build a report from a printer's snapshot series, oldest first.
+    pub fn from_snapshots(snapshots: &[PrinterData]) -> Self {
+        let pages_per_day = rate_per_day(snapshots, |d| d.pages_printed);
+        let subscription_impressions_per_day =
+            rate_per_day(snapshots, |d| d.subscription_impressions);
+
+        let black_rate = depletion_rate_per_second(snapshots, |d| d.black_ink_level);
+        let colour_rate = depletion_rate_per_second(snapshots, |d| d.colour_ink_level);
+        let latest = snapshots.last();
+
+        let black_ink_days_remaining = days_remaining(black_rate, latest.map(|d| d.black_ink_level));
+        let colour_ink_days_remaining =
+            days_remaining(colour_rate, latest.map(|d| d.colour_ink_level));
+
+        let latest_timestamp = latest.map(|d| d.timestamp);
+
+        Self {
+            snapshot_count: snapshots.len(),
+            pages_per_day,
+            subscription_impressions_per_day,
+            black_ink_days_remaining,
+            colour_ink_days_remaining,
+            black_ink_runs_out: runs_out_date(latest_timestamp, black_ink_days_remaining),
+            colour_ink_runs_out: runs_out_date(latest_timestamp, colour_ink_days_remaining),
+        }
+    }
+}
+
+fn days_remaining(rate_per_second: Option<f64>, current_level: Option<u32>) -> Option<f64> {
+    match (rate_per_second, current_level) {
+        (Some(rate), Some(level)) if rate > 0.0 => Some(level as f64 / (rate * 86400.0)),
+        _ => None,
+    }
+}
+
+/// Project forward from `from` (the snapshot the days-remaining figure was
+/// computed against), not from "now" — otherwise a stale latest snapshot
+/// silently pushes the runs-out date back by however long it's been since
+/// the last poll.
+fn runs_out_date(from: Option<DateTime<Utc>>, days_remaining: Option<f64>) -> Option<DateTime<Utc>> {
+    let from = from?;
+    let days = days_remaining?;
+    from.checked_add_signed(ChronoDuration::seconds((days * 86400.0) as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(hours_ago: i64, pages_printed: u32, black_ink_level: u32) -> PrinterData {
+        PrinterData {
+            timestamp: Utc::now() - ChronoDuration::hours(hours_ago),
+            pages_printed,
+            subscription_impressions: 0,
+            colour_ink_level: 100,
+            black_ink_level,
+            consumables: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn insufficient_data_with_fewer_than_two_snapshots() {
+        let report = HistoryReport::from_snapshots(&[snapshot(0, 10, 50)]);
+        assert_eq!(report.pages_per_day, None);
+        assert_eq!(report.black_ink_days_remaining, None);
+        assert_eq!(report.black_ink_runs_out, None);
+    }
+
+    #[test]
+    fn projects_runs_out_date_from_latest_snapshot_not_now() {
+        // Two snapshots 24h apart, dropping 10% black ink/day, with the
+        // latest snapshot itself 72h stale (the user hasn't polled since).
+        let snapshots = vec![snapshot(96, 0, 60), snapshot(72, 100, 50)];
+
+        let report = HistoryReport::from_snapshots(&snapshots);
+
+        let days_remaining = report.black_ink_days_remaining.expect("rate computed");
+        assert!((days_remaining - 5.0).abs() < 0.01);
+
+        let latest_timestamp = snapshots.last().unwrap().timestamp;
+        let expected = latest_timestamp + ChronoDuration::seconds((days_remaining * 86400.0) as i64);
+        let runs_out = report.black_ink_runs_out.expect("runs-out date computed");
+        assert_eq!(runs_out, expected);
+
+        // Anchored to the stale snapshot, not to "now" — so it must land
+        // well before `now + days_remaining`, which a `Utc::now()`-anchored
+        // bug would otherwise produce.
+        assert!(runs_out < Utc::now() + ChronoDuration::days(5));
+    }
+
+    #[test]
+    fn pages_per_day_uses_forward_deltas() {
+        let snapshots = vec![snapshot(48, 0, 80), snapshot(24, 50, 70), snapshot(0, 150, 60)];
+
+        let report = HistoryReport::from_snapshots(&snapshots);
+
+        // (50 - 0) + (150 - 50) = 150 pages over 48h -> 75 pages/day.
+        assert!((report.pages_per_day.unwrap() - 75.0).abs() < 0.01);
+    }
+}
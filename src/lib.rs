@@ -5,21 +5,37 @@ use quick_xml::de::from_str;
 use regex::Regex;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use std::time::Duration;
 
+pub mod history;
+
+/// A single named printer entry in the config, with an optional override of
+/// the global `timeout_seconds`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrinterEntry {
+    pub url: String,
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
-    pub printer_url: String,
+    #[serde(default)]
+    pub printers: HashMap<String, PrinterEntry>,
     pub timeout_seconds: u64,
     pub last_updated: Option<DateTime<Utc>>,
 }
 
+/// Name given to the lone printer migrated from a pre-multi-printer config.
+pub const DEFAULT_PRINTER_NAME: &str = "default";
+
 impl Default for Config {
     fn default() -> Self {
         Self {
-            printer_url: String::new(),
+            printers: HashMap::new(),
             timeout_seconds: 30,
             last_updated: None,
         }
@@ -30,14 +46,48 @@ impl Config {
     pub fn load() -> Result<Self> {
         let config_path = Self::get_config_path()?;
 
-        if config_path.exists() {
-            let content = fs::read_to_string(&config_path).context("Failed to read config file")?;
-            let config: Config =
-                serde_json::from_str(&content).context("Failed to parse config file")?;
-            Ok(config)
-        } else {
-            Ok(Config::default())
+        if !config_path.exists() {
+            return Ok(Config::default());
         }
+
+        let content = fs::read_to_string(&config_path).context("Failed to read config file")?;
+        let raw: serde_json::Value =
+            serde_json::from_str(&content).context("Failed to parse config file")?;
+
+        // Older configs stored a single top-level `printer_url` instead of the
+        // `printers` map. Migrate it into a "default" entry and persist the
+        // new shape so this only happens once.
+        if let Some(legacy_url) = raw.get("printer_url").and_then(|v| v.as_str()) {
+            let timeout_seconds = raw
+                .get("timeout_seconds")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(30);
+            let last_updated = raw
+                .get("last_updated")
+                .and_then(|v| serde_json::from_value(v.clone()).ok());
+
+            let mut printers = HashMap::new();
+            if !legacy_url.is_empty() {
+                printers.insert(
+                    DEFAULT_PRINTER_NAME.to_string(),
+                    PrinterEntry {
+                        url: legacy_url.to_string(),
+                        timeout_seconds: None,
+                    },
+                );
+            }
+
+            let config = Config {
+                printers,
+                timeout_seconds,
+                last_updated,
+            };
+            config.save()?;
+            return Ok(config);
+        }
+
+        let config: Config = serde_json::from_value(raw).context("Failed to parse config file")?;
+        Ok(config)
     }
 
     pub fn save(&self) -> Result<()> {
@@ -61,6 +111,22 @@ impl Config {
             anyhow::bail!("Could not determine config directory")
         }
     }
+
+    /// Add or overwrite a named printer entry.
+    pub fn add_printer(&mut self, name: String, url: String, timeout_seconds: Option<u64>) {
+        self.printers.insert(
+            name,
+            PrinterEntry {
+                url,
+                timeout_seconds,
+            },
+        );
+    }
+
+    /// Remove a named printer entry, returning whether it existed.
+    pub fn remove_printer(&mut self, name: &str) -> bool {
+        self.printers.remove(name).is_some()
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -73,6 +139,36 @@ pub enum HPPrinterError {
     ConfigError(String),
 }
 
+/// A single reported consumable (ink or toner cartridge) and its remaining
+/// level, as found in the printer's `ConsumableSubunit`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConsumableLevel {
+    pub marker_color: String,
+    pub label_code: Option<String>,
+    pub percentage_remaining: u32,
+}
+
+/// Marker colors that count towards `colour_ink_level`, whether the printer
+/// reports one combined cartridge or separate C/M/Y cartridges.
+const COLOUR_MARKERS: [&str; 4] = ["Cyan", "Magenta", "Yellow", "CyanMagentaYellow"];
+
+fn colour_ink_level(consumables: &[ConsumableLevel]) -> u32 {
+    consumables
+        .iter()
+        .filter(|c| COLOUR_MARKERS.contains(&c.marker_color.as_str()))
+        .map(|c| c.percentage_remaining)
+        .min()
+        .unwrap_or(0)
+}
+
+fn black_ink_level(consumables: &[ConsumableLevel]) -> u32 {
+    consumables
+        .iter()
+        .find(|c| c.marker_color == "Black")
+        .map(|c| c.percentage_remaining)
+        .unwrap_or(0)
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct PrinterData {
     pub timestamp: DateTime<Utc>,
@@ -80,21 +176,23 @@ pub struct PrinterData {
     pub subscription_impressions: u32,
     pub colour_ink_level: u32,
     pub black_ink_level: u32,
+    #[serde(default)]
+    pub consumables: Vec<ConsumableLevel>,
 }
 
 impl PrinterData {
     pub fn new(
         pages_printed: u32,
         subscription_impressions: u32,
-        colour_ink_level: u32,
-        black_ink_level: u32,
+        consumables: Vec<ConsumableLevel>,
     ) -> Self {
         Self {
             timestamp: Utc::now(),
             pages_printed,
             subscription_impressions,
-            colour_ink_level,
-            black_ink_level,
+            colour_ink_level: colour_ink_level(&consumables),
+            black_ink_level: black_ink_level(&consumables),
+            consumables,
         }
     }
 }
@@ -111,7 +209,6 @@ struct ConsumableSubunit {
 }
 
 #[derive(Debug, Deserialize)]
-#[allow(dead_code)]
 struct Consumable {
     #[serde(rename = "MarkerColor")]
     marker_color: String,
@@ -209,34 +306,31 @@ impl HPPrinterClient {
             HPPrinterError::XmlParsingError(e)
         })?;
 
-        let mut colour_ink = 0u32;
-        let mut black_ink = 0u32;
-
-        for consumable in &parsed.consumable_subunit.consumables {
-            if let Some(percentage) = &consumable.percentage_remaining {
-                match consumable.marker_color.as_str() {
-                    "CyanMagentaYellow" => {
-                        colour_ink = percentage.parse::<u32>().unwrap_or_else(|_| {
-                            warn!("Could not parse colour ink percentage: {percentage}");
-                            0
-                        });
-                    }
-                    "Black" => {
-                        black_ink = percentage.parse::<u32>().unwrap_or_else(|_| {
-                            warn!("Could not parse black ink percentage: {percentage}");
-                            0
-                        });
-                    }
-                    _ => debug!("Unknown marker color: {}", consumable.marker_color),
-                }
-            }
-        }
+        let consumables: Vec<ConsumableLevel> = parsed
+            .consumable_subunit
+            .consumables
+            .iter()
+            .filter_map(|consumable| {
+                let percentage = consumable.percentage_remaining.as_ref()?;
+                let percentage_remaining = percentage.parse::<u32>().unwrap_or_else(|_| {
+                    warn!(
+                        "Could not parse percentage for {}: {percentage}",
+                        consumable.marker_color
+                    );
+                    0
+                });
+                Some(ConsumableLevel {
+                    marker_color: consumable.marker_color.clone(),
+                    label_code: consumable.label_code.clone(),
+                    percentage_remaining,
+                })
+            })
+            .collect();
 
         Ok(PrinterData::new(
             pages_printed,
             subscription_impressions,
-            colour_ink,
-            black_ink,
+            consumables,
         ))
     }
 
@@ -1,16 +1,31 @@
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use chrono_tz::Europe::Stockholm;
-use clap::{Parser, ValueEnum};
-use colored::*;
-use log::{debug, error, info};
-use tabled::{settings::Style, Table, Tabled};
+use clap::Parser;
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use std::time::Duration;
+use tabled::Tabled;
 
+mod output;
+
+use hp_instant_ink_cli::history::{self, HistoryReport};
 use hp_instant_ink_cli::{
-    format_json_output, Config, HPPrinterClient, HPPrinterError, PrinterData,
+    format_json_output, Config, HPPrinterClient, HPPrinterError, PrinterData, DEFAULT_PRINTER_NAME,
 };
+use output::{OutputFormat, Renderer};
+
+/// Human-readable label for a raw `MarkerColor` value from the printer.
+fn consumable_label(marker_color: &str) -> String {
+    match marker_color {
+        "CyanMagentaYellow" => "Colour (CMY)".to_string(),
+        "PhotoBlack" => "Photo Black".to_string(),
+        other => other.to_string(),
+    }
+}
 
 fn create_table_data(data: &PrinterData) -> Vec<PrinterDataTable> {
-    vec![
+    let mut rows = vec![
         PrinterDataTable {
             metric: "Subscription Pages".to_string(),
             value: data.subscription_impressions.to_string(),
@@ -19,23 +34,28 @@ fn create_table_data(data: &PrinterData) -> Vec<PrinterDataTable> {
             metric: "Total Pages".to_string(),
             value: data.pages_printed.to_string(),
         },
-        PrinterDataTable {
-            metric: "Colour Ink Remaining".to_string(),
-            value: format!("{}%", data.colour_ink_level),
-        },
-        PrinterDataTable {
-            metric: "Black Ink Remaining".to_string(),
-            value: format!("{}%", data.black_ink_level),
-        },
-        PrinterDataTable {
-            metric: "Last Updated".to_string(),
-            value: data
-                .timestamp
-                .with_timezone(&Stockholm)
-                .format("%Y-%m-%d %H:%M:%S %Z")
-                .to_string(),
-        },
-    ]
+    ];
+
+    for consumable in &data.consumables {
+        rows.push(PrinterDataTable {
+            metric: format!(
+                "{} Ink Remaining",
+                consumable_label(&consumable.marker_color)
+            ),
+            value: format!("{}%", consumable.percentage_remaining),
+        });
+    }
+
+    rows.push(PrinterDataTable {
+        metric: "Last Updated".to_string(),
+        value: data
+            .timestamp
+            .with_timezone(&Stockholm)
+            .format("%Y-%m-%d %H:%M:%S %Z")
+            .to_string(),
+    });
+
+    rows
 }
 
 #[derive(Tabled)]
@@ -46,17 +66,11 @@ struct PrinterDataTable {
     value: String,
 }
 
-#[derive(ValueEnum, Clone, Debug)]
-enum OutputFormat {
-    Table,
-    Json,
-}
-
 #[derive(Parser, Debug)]
 #[command(
     name = "hp-instant-ink-cli",
     about = "HP Instant Ink CLI Tool - Query HP printer status and ink levels",
-    long_about = "This CLI tool queries HP printers locally to obtain page usage and ink levels.\n\nExamples:\n  hp-instant-ink-cli --printer 192.168.1.13\n  hp-instant-ink-cli --printer hp-printer.local --format json\n  hp-instant-ink-cli config --set-printer 192.168.1.13\n  hp-instant-ink-cli config --show"
+    long_about = "This CLI tool queries HP printers locally to obtain page usage and ink levels.\n\nExamples:\n  hp-instant-ink-cli --printer 192.168.1.13\n  hp-instant-ink-cli --printer hp-printer.local --format json\n  hp-instant-ink-cli config --set-printer 192.168.1.13\n  hp-instant-ink-cli config --show\n  hp-instant-ink-cli config --add-printer office 192.168.1.13\n  hp-instant-ink-cli history\n  hp-instant-ink-cli --watch --interval 300\n  hp-instant-ink-cli --max-width 80 --no-color"
 )]
 struct Args {
     #[command(subcommand)]
@@ -65,12 +79,13 @@ struct Args {
     #[arg(
         short,
         long,
+        global = true,
         help = "Printer URL/hostname/IP (will auto-add /DevMgmt/ProductUsageDyn.xml)",
         value_name = "HOST"
     )]
     printer: Option<String>,
 
-    #[arg(short, long, value_enum, help = "Output format")]
+    #[arg(short, long, global = true, value_enum, help = "Output format")]
     format: Option<OutputFormat>,
 
     #[arg(short, long, help = "Request timeout in seconds")]
@@ -78,6 +93,35 @@ struct Args {
 
     #[arg(short, long, help = "Enable verbose logging")]
     verbose: bool,
+
+    #[arg(
+        long,
+        help = "Continuously poll the printer on a fixed interval instead of querying once"
+    )]
+    watch: bool,
+
+    #[arg(
+        long,
+        help = "Polling interval in seconds for --watch",
+        value_name = "SECONDS",
+        default_value_t = 60
+    )]
+    interval: u64,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Wrap table output to at most N columns wide",
+        value_name = "N"
+    )]
+    max_width: Option<usize>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Disable colored output (also honored via the NO_COLOR env var)"
+    )]
+    no_color: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -86,9 +130,24 @@ enum Command {
         #[arg(long, help = "Show current configuration")]
         show: bool,
 
-        #[arg(long, help = "Set default printer", value_name = "HOST")]
+        #[arg(
+            long,
+            help = "Set default printer (shorthand for --add-printer default <HOST>)",
+            value_name = "HOST"
+        )]
         set_printer: Option<String>,
 
+        #[arg(
+            long,
+            help = "Add or update a named printer",
+            num_args = 2,
+            value_names = ["NAME", "HOST"]
+        )]
+        add_printer: Option<Vec<String>>,
+
+        #[arg(long, help = "Remove a named printer", value_name = "NAME")]
+        remove_printer: Option<String>,
+
         #[arg(long, help = "Set default timeout", value_name = "SECONDS")]
         set_timeout: Option<u64>,
 
@@ -98,13 +157,78 @@ enum Command {
         #[arg(long, help = "Reset configuration to defaults")]
         reset: bool,
     },
+
+    /// Report page and ink usage trends from previously recorded snapshots.
+    History,
 }
 
-fn format_table_output(data: &PrinterData) -> Result<String> {
+fn format_table_output(data: &PrinterData, renderer: &Renderer) -> Result<String> {
     let table_data = create_table_data(data);
-    let mut table = Table::new(table_data);
-    table.with(Style::rounded());
-    Ok(table.to_string())
+    Ok(renderer.table(table_data))
+}
+
+#[derive(Tabled)]
+struct HistoryTableRow {
+    #[tabled(rename = "Metric")]
+    metric: String,
+    #[tabled(rename = "Value")]
+    value: String,
+}
+
+fn format_history_table(report: &HistoryReport, renderer: &Renderer) -> Result<String> {
+    let rows = vec![
+        HistoryTableRow {
+            metric: "Snapshots".to_string(),
+            value: report.snapshot_count.to_string(),
+        },
+        HistoryTableRow {
+            metric: "Pages/Day".to_string(),
+            value: format_rate(report.pages_per_day, "pages/day"),
+        },
+        HistoryTableRow {
+            metric: "Subscription Impressions/Day".to_string(),
+            value: format_rate(report.subscription_impressions_per_day, "impressions/day"),
+        },
+        HistoryTableRow {
+            metric: "Black Ink Days Remaining".to_string(),
+            value: format_days(report.black_ink_days_remaining),
+        },
+        HistoryTableRow {
+            metric: "Black Ink Runs Out".to_string(),
+            value: format_date(report.black_ink_runs_out),
+        },
+        HistoryTableRow {
+            metric: "Colour Ink Days Remaining".to_string(),
+            value: format_days(report.colour_ink_days_remaining),
+        },
+        HistoryTableRow {
+            metric: "Colour Ink Runs Out".to_string(),
+            value: format_date(report.colour_ink_runs_out),
+        },
+    ];
+
+    Ok(renderer.table(rows))
+}
+
+fn format_rate(rate: Option<f64>, unit: &str) -> String {
+    match rate {
+        Some(r) => format!("{r:.1} {unit}"),
+        None => "insufficient data".to_string(),
+    }
+}
+
+fn format_days(days: Option<f64>) -> String {
+    match days {
+        Some(d) => format!("{d:.1} days"),
+        None => "insufficient data".to_string(),
+    }
+}
+
+fn format_date(date: Option<DateTime<Utc>>) -> String {
+    match date {
+        Some(d) => d.with_timezone(&Stockholm).format("%Y-%m-%d").to_string(),
+        None => "insufficient data".to_string(),
+    }
 }
 
 fn setup_logging(verbose: bool) {
@@ -114,7 +238,7 @@ fn setup_logging(verbose: bool) {
         .init();
 }
 
-fn print_alerts(data: &PrinterData) {
+fn low_ink_alerts(data: &PrinterData) -> Vec<String> {
     let mut alerts = Vec::new();
 
     if data.colour_ink_level <= 20 {
@@ -131,19 +255,291 @@ fn print_alerts(data: &PrinterData) {
         ));
     }
 
-    if !alerts.is_empty() {
-        eprintln!("\n{}", "ALERTS:".red().bold());
-        for alert in alerts {
-            eprintln!("  {}", alert.yellow());
+    alerts
+}
+
+fn print_alerts(data: &PrinterData, renderer: &Renderer) {
+    let alerts: Vec<(Option<String>, String)> = low_ink_alerts(data)
+        .into_iter()
+        .map(|alert| (None, alert))
+        .collect();
+    renderer.print_alerts(&alerts);
+}
+
+/// Resolve a single target printer (and its timeout) from an explicit
+/// `--printer` override or, failing that, a config with exactly one entry.
+/// Returns `None` when there's no explicit printer and the config holds zero
+/// or multiple printers, in which case the caller decides what to do next.
+fn resolve_single_printer(
+    explicit: Option<&str>,
+    config: &Config,
+    timeout_override: Option<u64>,
+) -> Option<(String, u64)> {
+    if let Some(printer) = explicit {
+        let url = HPPrinterClient::normalize_printer_url(printer);
+        let timeout = timeout_override.unwrap_or(config.timeout_seconds);
+        return Some((url, timeout));
+    }
+
+    if config.printers.len() == 1 {
+        let entry = config.printers.values().next().unwrap();
+        let timeout = timeout_override
+            .or(entry.timeout_seconds)
+            .unwrap_or(config.timeout_seconds);
+        return Some((entry.url.clone(), timeout));
+    }
+
+    None
+}
+
+#[derive(Tabled)]
+struct AggregatedPrinterRow {
+    #[tabled(rename = "Printer")]
+    printer: String,
+    #[tabled(rename = "Subscription Pages")]
+    subscription_pages: String,
+    #[tabled(rename = "Total Pages")]
+    total_pages: String,
+    #[tabled(rename = "Colour Ink")]
+    colour_ink: String,
+    #[tabled(rename = "Black Ink")]
+    black_ink: String,
+    #[tabled(rename = "Last Updated")]
+    last_updated: String,
+}
+
+fn aggregated_row(name: &str, result: &Result<PrinterData, HPPrinterError>) -> AggregatedPrinterRow {
+    match result {
+        Ok(data) => AggregatedPrinterRow {
+            printer: name.to_string(),
+            subscription_pages: data.subscription_impressions.to_string(),
+            total_pages: data.pages_printed.to_string(),
+            colour_ink: format!("{}%", data.colour_ink_level),
+            black_ink: format!("{}%", data.black_ink_level),
+            last_updated: data
+                .timestamp
+                .with_timezone(&Stockholm)
+                .format("%Y-%m-%d %H:%M:%S %Z")
+                .to_string(),
+        },
+        Err(e) => AggregatedPrinterRow {
+            printer: name.to_string(),
+            subscription_pages: "-".to_string(),
+            total_pages: "-".to_string(),
+            colour_ink: "-".to_string(),
+            black_ink: "-".to_string(),
+            last_updated: format!("error: {e}"),
+        },
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct AggregatedPrinterResult {
+    printer: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<PrinterData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Query every configured printer concurrently and return `(name, url, result)`
+/// triples sorted by name.
+async fn query_all_printers(
+    config: &Config,
+    timeout_override: Option<u64>,
+) -> Vec<(String, String, Result<PrinterData, HPPrinterError>)> {
+    let mut handles = Vec::new();
+
+    for (name, entry) in &config.printers {
+        let name = name.clone();
+        let url = entry.url.clone();
+        let timeout = timeout_override
+            .or(entry.timeout_seconds)
+            .unwrap_or(config.timeout_seconds);
+        let task_url = url.clone();
+
+        handles.push(tokio::spawn(async move {
+            let result = match HPPrinterClient::new(task_url, timeout) {
+                Ok(client) => client.get_printer_data().await,
+                Err(e) => Err(HPPrinterError::ConfigError(e.to_string())),
+            };
+            (name, url, result)
+        }));
+    }
+
+    let mut results = Vec::new();
+    for handle in handles {
+        match handle.await {
+            Ok(triple) => results.push(triple),
+            Err(e) => error!("Printer query task panicked: {e}"),
+        }
+    }
+
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    results
+}
+
+async fn handle_multi_printer_query(
+    config: &Config,
+    timeout_override: Option<u64>,
+    renderer: &Renderer,
+) -> Result<()> {
+    let results = query_all_printers(config, timeout_override).await;
+
+    match renderer.format {
+        OutputFormat::Json => {
+            let payload: Vec<AggregatedPrinterResult> = results
+                .iter()
+                .map(|(name, _url, result)| match result {
+                    Ok(data) => AggregatedPrinterResult {
+                        printer: name.clone(),
+                        data: Some(data.clone()),
+                        error: None,
+                    },
+                    Err(e) => AggregatedPrinterResult {
+                        printer: name.clone(),
+                        data: None,
+                        error: Some(e.to_string()),
+                    },
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&payload)?);
+        }
+        OutputFormat::Table => {
+            let rows: Vec<AggregatedPrinterRow> = results
+                .iter()
+                .map(|(name, _url, result)| aggregated_row(name, result))
+                .collect();
+            println!("{}", renderer.table(rows));
+        }
+    }
+
+    let mut alerts: Vec<(Option<String>, String)> = Vec::new();
+    for (name, _url, result) in &results {
+        if let Ok(data) = result {
+            alerts.extend(
+                low_ink_alerts(data)
+                    .into_iter()
+                    .map(|alert| (Some(name.clone()), alert)),
+            );
+        }
+    }
+    renderer.print_alerts(&alerts);
+
+    for (_name, url, result) in &results {
+        if let Ok(data) = result {
+            if let Err(e) = history::append_snapshot(url, data) {
+                warn!("Failed to record history snapshot for {url}: {e}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Alerts for thresholds that newly dropped to or below 20% since `previous`,
+/// so a steady-state low-ink printer doesn't re-alert on every poll.
+fn crossed_low_ink_alerts(previous: &PrinterData, current: &PrinterData) -> Vec<String> {
+    let mut alerts = Vec::new();
+
+    if previous.colour_ink_level > 20 && current.colour_ink_level <= 20 {
+        alerts.push(format!(
+            "LOW COLOUR INK: {}% remaining",
+            current.colour_ink_level
+        ));
+    }
+
+    if previous.black_ink_level > 20 && current.black_ink_level <= 20 {
+        alerts.push(format!(
+            "LOW BLACK INK: {}% remaining",
+            current.black_ink_level
+        ));
+    }
+
+    alerts
+}
+
+async fn run_watch_loop(
+    client: &HPPrinterClient,
+    printer_url: &str,
+    renderer: &Renderer,
+    interval_seconds: u64,
+) -> Result<()> {
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval_seconds));
+    let mut previous: Option<PrinterData> = None;
+
+    loop {
+        ticker.tick().await;
+
+        match client.get_printer_data().await {
+            Ok(data) => {
+                print!("\x1B[2J\x1B[1;1H");
+
+                let output = match renderer.format {
+                    OutputFormat::Json => format_json_output(&data)?,
+                    OutputFormat::Table => format_table_output(&data, renderer)?,
+                };
+                println!("{output}");
+
+                if let Some(previous) = &previous {
+                    let alerts: Vec<(Option<String>, String)> =
+                        crossed_low_ink_alerts(previous, &data)
+                            .into_iter()
+                            .map(|alert| (None, alert))
+                            .collect();
+                    renderer.print_alerts(&alerts);
+                } else {
+                    print_alerts(&data, renderer);
+                }
+
+                if let Err(e) = history::append_snapshot(printer_url, &data) {
+                    warn!("Failed to record history snapshot: {e}");
+                }
+
+                previous = Some(data);
+            }
+            Err(e) => {
+                warn!("Poll failed, will retry on the next interval: {e}");
+            }
         }
     }
 }
 
-async fn handle_config_command(config_args: Command) -> Result<()> {
+async fn handle_history_command(printer: Option<String>, renderer: &Renderer) -> Result<()> {
+    let config = Config::load()?;
+
+    let printer_url = match resolve_single_printer(printer.as_deref(), &config, None) {
+        Some((url, _timeout)) => url,
+        None if config.printers.is_empty() => {
+            error!("No printer specified. Use --printer <host> or add one with 'config --add-printer <name> <host>'");
+            std::process::exit(1);
+        }
+        None => {
+            error!("Multiple printers configured; specify which one with --printer <host>");
+            std::process::exit(1);
+        }
+    };
+
+    let snapshots = history::load_snapshots(&printer_url)?;
+    let report = HistoryReport::from_snapshots(&snapshots);
+
+    let output = match renderer.format {
+        OutputFormat::Json => serde_json::to_string_pretty(&report)?,
+        OutputFormat::Table => format_history_table(&report, renderer)?,
+    };
+
+    println!("{output}");
+
+    Ok(())
+}
+
+async fn handle_config_command(config_args: Command, renderer: &Renderer) -> Result<()> {
     match config_args {
         Command::Config {
             show,
             set_printer,
+            add_printer,
+            remove_printer,
             set_timeout,
             set_format,
             reset,
@@ -153,12 +549,12 @@ async fn handle_config_command(config_args: Command) -> Result<()> {
             if reset {
                 config = Config::default();
                 config.save()?;
-                println!("{}", "Configuration reset to defaults".green());
+                println!("{}", renderer.success("Configuration reset to defaults"));
                 return Ok(());
             }
 
             if show {
-                println!("{}", "Current configuration:".blue().bold());
+                println!("{}", renderer.heading("Current configuration:"));
                 let config_json = serde_json::to_string_pretty(&config)?;
                 println!("{config_json}");
                 return Ok(());
@@ -168,30 +564,59 @@ async fn handle_config_command(config_args: Command) -> Result<()> {
 
             if let Some(printer) = set_printer {
                 let normalized = HPPrinterClient::normalize_printer_url(&printer);
-                config.printer_url = normalized.clone();
+                config.add_printer(DEFAULT_PRINTER_NAME.to_string(), normalized.clone(), None);
+                changed = true;
+                println!(
+                    "{} {normalized}",
+                    renderer.success("Set default printer:")
+                );
+            }
+
+            if let Some(values) = add_printer {
+                let name = values[0].clone();
+                let normalized = HPPrinterClient::normalize_printer_url(&values[1]);
+                config.add_printer(name.clone(), normalized.clone(), None);
                 changed = true;
-                println!("{} {}", "Set default printer:".green(), normalized);
+                println!(
+                    "{} {name} -> {normalized}",
+                    renderer.success("Added printer:")
+                );
+            }
+
+            if let Some(name) = remove_printer {
+                if config.remove_printer(&name) {
+                    changed = true;
+                    println!("{} {name}", renderer.success("Removed printer:"));
+                } else {
+                    println!("{} {name}", renderer.warning("No such printer:"));
+                }
             }
 
             if let Some(timeout) = set_timeout {
                 config.timeout_seconds = timeout;
                 changed = true;
-                println!("{} {}", "Set default timeout:".green(), timeout);
+                println!("{} {timeout}", renderer.success("Set default timeout:"));
             }
 
             if set_format.is_some() {
-                println!("{}", "Note: Format configuration is no longer supported in config. Use --format flag.".yellow());
+                println!(
+                    "{}",
+                    renderer.warning(
+                        "Note: Format configuration is no longer supported in config. Use --format flag."
+                    )
+                );
             }
 
             if changed {
                 config.save()?;
-                println!("{}", "Configuration saved".green());
+                println!("{}", renderer.success("Configuration saved"));
             } else {
                 println!("No configuration changes made. Use --help to see available options.");
             }
 
             Ok(())
         }
+        Command::History => unreachable!("History is dispatched directly in main()"),
     }
 }
 
@@ -201,8 +626,14 @@ async fn main() -> Result<()> {
 
     setup_logging(args.verbose);
 
+    let format = args.format.unwrap_or(OutputFormat::Table);
+    let renderer = Renderer::new(format, args.max_width, args.no_color);
+
     if let Some(command) = args.command {
-        return handle_config_command(command).await;
+        return match command {
+            Command::History => handle_history_command(args.printer.clone(), &renderer).await,
+            other => handle_config_command(other, &renderer).await,
+        };
     }
 
     info!("HP Instant Ink CLI Tool starting");
@@ -211,36 +642,49 @@ async fn main() -> Result<()> {
     let config = Config::load()?;
     debug!("Loaded config: {config:?}");
 
-    let printer_url = if let Some(printer) = args.printer {
-        HPPrinterClient::normalize_printer_url(&printer)
-    } else if !config.printer_url.is_empty() {
-        config.printer_url
-    } else {
-        error!("No printer specified. Use --printer <host> or set a default with 'config --set-printer <host>'");
-        error!("Example: hp-instant-ink-cli --printer 192.168.1.13");
-        error!("         hp-instant-ink-cli config --set-printer 192.168.1.13");
-        std::process::exit(1);
-    };
-
-    let timeout = args.timeout.unwrap_or(config.timeout_seconds);
-    let format = args.format.unwrap_or(OutputFormat::Table);
+    let (printer_url, timeout) =
+        match resolve_single_printer(args.printer.as_deref(), &config, args.timeout) {
+            Some(resolved) => resolved,
+            None if config.printers.is_empty() => {
+                error!("No printer specified. Use --printer <host> or add one with 'config --add-printer <name> <host>'");
+                error!("Example: hp-instant-ink-cli --printer 192.168.1.13");
+                error!("         hp-instant-ink-cli config --add-printer office 192.168.1.13");
+                std::process::exit(1);
+            }
+            None => {
+                if args.watch {
+                    error!("--watch doesn't yet support multiple configured printers; specify one with --printer <host>");
+                    std::process::exit(1);
+                }
+                return handle_multi_printer_query(&config, args.timeout, &renderer).await;
+            }
+        };
 
     info!("Using printer: {printer_url}");
-    debug!("Settings - timeout: {timeout}s, format: {format:?}");
+    debug!("Settings - timeout: {timeout}s, format: {:?}", renderer.format);
 
     let client = HPPrinterClient::new(printer_url.clone(), timeout)
         .context("Failed to create HP printer client")?;
 
+    if args.watch {
+        info!("Watching printer every {}s (Ctrl+C to stop)", args.interval);
+        return run_watch_loop(&client, &printer_url, &renderer, args.interval).await;
+    }
+
     match client.get_printer_data().await {
         Ok(data) => {
-            let output = match format {
+            let output = match renderer.format {
                 OutputFormat::Json => format_json_output(&data)?,
-                OutputFormat::Table => format_table_output(&data)?,
+                OutputFormat::Table => format_table_output(&data, &renderer)?,
             };
 
             println!("{output}");
 
-            print_alerts(&data);
+            print_alerts(&data, &renderer);
+
+            if let Err(e) = history::append_snapshot(&printer_url, &data) {
+                warn!("Failed to record history snapshot: {e}");
+            }
 
             info!("Successfully retrieved printer data");
         }
@@ -0,0 +1,102 @@
+use clap::ValueEnum;
+use colored::Colorize;
+use std::io::IsTerminal;
+use tabled::{
+    settings::{Style, Width},
+    Table, Tabled,
+};
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
+
+/// Single place all CLI rendering flows through, so `--max-width` and
+/// `--no-color` behave the same for every table, alert, and status message
+/// instead of each call site deciding for itself.
+pub struct Renderer {
+    pub format: OutputFormat,
+    max_width: Option<usize>,
+    stdout_color_enabled: bool,
+    stderr_color_enabled: bool,
+}
+
+impl Renderer {
+    pub fn new(format: OutputFormat, max_width: Option<usize>, no_color: bool) -> Self {
+        let no_color = no_color || std::env::var_os("NO_COLOR").is_some();
+        let stdout_color_enabled = !no_color && std::io::stdout().is_terminal();
+        let stderr_color_enabled = !no_color && std::io::stderr().is_terminal();
+
+        Self {
+            format,
+            max_width,
+            stdout_color_enabled,
+            stderr_color_enabled,
+        }
+    }
+
+    /// Render `rows` as a rounded table, wrapping cell content to fit
+    /// `--max-width` columns when one was given.
+    pub fn table<T: Tabled>(&self, rows: Vec<T>) -> String {
+        let mut table = Table::new(rows);
+        table.with(Style::rounded());
+        if let Some(width) = self.max_width {
+            table.with(Width::wrap(width));
+        }
+        table.to_string()
+    }
+
+    pub fn heading(&self, text: &str) -> String {
+        self.paint_stdout(text, |t| t.blue().bold().to_string())
+    }
+
+    pub fn success(&self, text: &str) -> String {
+        self.paint_stdout(text, |t| t.green().to_string())
+    }
+
+    pub fn warning(&self, text: &str) -> String {
+        self.paint_stdout(text, |t| t.yellow().to_string())
+    }
+
+    fn paint_stdout(&self, text: &str, colorize: impl FnOnce(&str) -> String) -> String {
+        Self::paint(self.stdout_color_enabled, text, colorize)
+    }
+
+    fn paint_stderr(&self, text: &str, colorize: impl FnOnce(&str) -> String) -> String {
+        Self::paint(self.stderr_color_enabled, text, colorize)
+    }
+
+    fn paint(enabled: bool, text: &str, colorize: impl FnOnce(&str) -> String) -> String {
+        if enabled {
+            colorize(text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Print a red "ALERTS:" header followed by each alert in yellow. Each
+    /// alert may carry a source name (e.g. a printer name when aggregating
+    /// several printers); pass `None` when there's only one source.
+    ///
+    /// Colored independently of table/status output since alerts go to
+    /// stderr, which may be redirected to a log file even when stdout is
+    /// still an interactive terminal (or vice versa).
+    pub fn print_alerts(&self, alerts: &[(Option<String>, String)]) {
+        if alerts.is_empty() {
+            return;
+        }
+
+        eprintln!(
+            "\n{}",
+            self.paint_stderr("ALERTS:", |t| t.red().bold().to_string())
+        );
+        for (source, alert) in alerts {
+            let line = self.paint_stderr(alert, |t| t.yellow().to_string());
+            match source {
+                Some(name) => eprintln!("  {name}: {line}"),
+                None => eprintln!("  {line}"),
+            }
+        }
+    }
+}